@@ -1,13 +1,14 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
     error::Error,
     ffi::OsStr,
     fmt::{Debug, Display},
-    fs::File,
-    hash::Hash,
+    fs::{self, File},
+    hash::{Hash, Hasher},
     io::Read,
-    path::Path,
+    path::{Path, PathBuf},
     sync::LazyLock,
+    time::UNIX_EPOCH,
 };
 
 use object::{Object, ObjectSymbol};
@@ -35,7 +36,10 @@ fn main() {
     println!("cargo:rustc-link-search=native={}", lib.display());
     println!("cargo:rustc-link-lib=static=tinkwrap");
 
-    let all_libs = find_libs(Path::new(&lib.display().to_string()));
+    let format =
+        LibFormat::for_target_env(&std::env::var("CARGO_CFG_TARGET_ENV").unwrap_or_default());
+    let cache = ArchiveCache::new(std::env::var("OUT_DIR").expect("OUT_DIR not set"));
+    let all_libs = find_libs(Path::new(&lib.display().to_string()), format, Some(&cache));
 
     all_libs
         .all_symbols
@@ -53,11 +57,20 @@ fn main() {
         println!("Found static lib: {}", lib);
     }
 
+    let unresolved = find_unresolved_symbols(&all_libs.all_symbols);
+    link_system_libs(&unresolved);
+    if !unresolved.unresolved.is_empty() {
+        println!("cargo:warning={}", unresolved.describe());
+    }
+
     generate_dependancy_graph(all_libs);
 }
 
 fn generate_dependancy_graph(libs: AllLibs) {
-    let mut dep_graph = Graph::<LibInfo, u8>::new();
+    // Edge weight is the demangled symbol name that created the dependency,
+    // so the rendered graph reads as e.g. `BlobstoreClient::new_blobstore_client()`
+    // rather than `_ZN14BlobstoreClient...`.
+    let mut dep_graph = Graph::<LibInfo, String>::new();
 
     let mut index_to_lib_map: HashMap<NodeIndex, LibInfo> = HashMap::new();
     let mut lib_to_index_map: HashMap<LibInfo, NodeIndex> = HashMap::new();
@@ -67,9 +80,13 @@ fn generate_dependancy_graph(libs: AllLibs) {
         index_to_lib_map.insert(index, lib);
     }
 
-    let mut dependencies: HashSet<Dependency> = HashSet::new();
+    let mut dependencies: HashMap<Dependency, String> = HashMap::new();
     for (symbol, dependent) in libs.all_symbols.undefined {
-        let Some(dependency) = get_lib_for_symbol(&symbol, &libs.all_symbols.defined) else {
+        let Some(dependency) = get_lib_for_symbol(
+            &symbol,
+            &libs.all_symbols.defined,
+            &libs.all_symbols.weak_defined,
+        ) else {
             continue;
         };
 
@@ -85,16 +102,37 @@ fn generate_dependancy_graph(libs: AllLibs) {
             continue;
         }
 
-        dependencies.insert(Dependency {
-            dependent: *dependent_index,
-            dependency: *dependency_index,
-        });
+        dependencies
+            .entry(Dependency {
+                dependent: *dependent_index,
+                dependency: *dependency_index,
+            })
+            .or_insert_with(|| demangle(&symbol.symbol));
     }
 
-    for dep in dependencies {
-        dep_graph.add_edge(dep.dependent, dep.dependency, 0);
+    for (dep, symbol) in dependencies {
+        dep_graph.add_edge(dep.dependent, dep.dependency, symbol);
     }
-    println!("{:?}", Dot::with_config(&dep_graph, &[Config::EdgeNoLabel]));
+    println!("{:?}", Dot::with_config(&dep_graph, &[]));
+}
+
+/// Demangle a symbol name for diagnostics: try C++ (Itanium) demangling
+/// first, since this crate exists to link a C++ library through `cxx` and
+/// virtually every interesting symbol is `_ZN`-mangled, then Rust demangling,
+/// falling back to the raw (lossy-utf8) name when neither applies.
+fn demangle(symbol: &[u8]) -> String {
+    if let Ok(cpp_symbol) = cpp_demangle::Symbol::new(symbol) {
+        if let Ok(demangled) = cpp_symbol.demangle(&cpp_demangle::DemangleOptions::default()) {
+            return demangled;
+        }
+    }
+
+    let raw = String::from_utf8_lossy(symbol);
+    if let Ok(rust_symbol) = rustc_demangle::try_demangle(&raw) {
+        return rust_symbol.to_string();
+    }
+
+    raw.into_owned()
 }
 
 struct Dependency {
@@ -120,47 +158,199 @@ impl Eq for Dependency {}
 fn get_lib_for_symbol(
     symbol: &UnDefinedSymbol,
     defined_libs: &HashMap<DefinedSymbol, LibInfo>,
+    weak_defined_libs: &HashMap<DefinedSymbol, LibInfo>,
 ) -> Option<LibInfo> {
-    defined_libs.get(&DefinedSymbol::from(symbol)).cloned()
+    let key = DefinedSymbol::from(symbol);
+    defined_libs
+        .get(&key)
+        .or_else(|| weak_defined_libs.get(&key))
+        .cloned()
+}
+
+/// Well-known libc/runtime symbols mapped to the system library that
+/// provides them, so common external references don't get reported as
+/// genuinely unresolved. Prefixes end in `_` and match by prefix; anything
+/// else matches the symbol name exactly.
+static SYSTEM_SYMBOLS: LazyLock<Vec<(&'static str, &'static str)>> = LazyLock::new(|| {
+    vec![
+        ("pthread_", "pthread"),
+        ("dlopen", "dl"),
+        ("dlsym", "dl"),
+        ("dlclose", "dl"),
+        ("dladdr", "dl"),
+        ("sin", "m"),
+        ("cos", "m"),
+        ("tan", "m"),
+        ("sqrt", "m"),
+        ("pow", "m"),
+        ("exp", "m"),
+        ("log", "m"),
+        ("clock_gettime", "rt"),
+        ("shm_open", "rt"),
+        ("shm_unlink", "rt"),
+    ]
+});
+
+fn system_lib_for_symbol(name: &str) -> Option<&'static str> {
+    SYSTEM_SYMBOLS.iter().find_map(|(pattern, lib)| {
+        if let Some(prefix) = pattern.strip_suffix('_') {
+            name.starts_with(prefix).then_some(*lib)
+        } else {
+            (name == *pattern).then_some(*lib)
+        }
+    })
 }
 
-fn generate_lookup_tables<I>(libs: I) -> AllSymbols
+/// The result of scanning for undefined symbols that no discovered archive satisfies.
+#[derive(Clone, Debug, Default)]
+struct UnresolvedReport {
+    /// Undefined symbols with no known provider, grouped by the library that needs them.
+    unresolved: HashMap<LibInfo, Vec<UnDefinedSymbol>>,
+    /// System (dylib) libraries that satisfy the well-known symbols found,
+    /// e.g. `pthread`, `m`, `dl`, `rt`.
+    system_libs: HashSet<String>,
+}
+
+impl UnresolvedReport {
+    /// Human-readable summary suitable for a `cargo:warning=` line.
+    fn describe(&self) -> String {
+        self.unresolved
+            .iter()
+            .map(|(lib, symbols)| {
+                format!(
+                    "{} unresolved symbol(s) needed by {lib}: {}",
+                    symbols.len(),
+                    symbols
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// Scan `all_symbols.undefined` for references satisfied by neither a strong
+/// nor a weak definition in any discovered archive. Symbols matching
+/// `SYSTEM_SYMBOLS` are classified as system-library needs instead of
+/// genuinely unresolved.
+fn find_unresolved_symbols(all_symbols: &AllSymbols) -> UnresolvedReport {
+    let mut report = UnresolvedReport::default();
+
+    for (symbol, dependent) in &all_symbols.undefined {
+        let key = DefinedSymbol::from(symbol);
+        if all_symbols.defined.contains_key(&key) || all_symbols.weak_defined.contains_key(&key) {
+            continue;
+        }
+
+        let name = String::from_utf8_lossy(&symbol.symbol);
+        if let Some(system_lib) = system_lib_for_symbol(&name) {
+            report.system_libs.insert(system_lib.to_owned());
+            continue;
+        }
+
+        report
+            .unresolved
+            .entry(dependent.clone())
+            .or_default()
+            .push(symbol.clone());
+    }
+
+    report
+}
+
+/// Emit `cargo:rustc-link-lib=dylib=...` for every system library identified
+/// by `find_unresolved_symbols`.
+fn link_system_libs(report: &UnresolvedReport) {
+    for lib in &report.system_libs {
+        println!("cargo:rustc-link-lib=dylib={lib}");
+    }
+}
+
+fn generate_lookup_tables<I>(libs: I, cache: Option<&ArchiveCache>) -> AllSymbols
 where
     I: IntoIterator<Item = LibInfo>,
 {
     let mut defined_table: HashMap<DefinedSymbol, LibInfo> = HashMap::new();
-    let mut undefined_table: HashMap<UnDefinedSymbol, LibInfo> = HashMap::new();
+    let mut weak_defined_table: HashMap<DefinedSymbol, LibInfo> = HashMap::new();
+    let mut undefined_table: Vec<(UnDefinedSymbol, LibInfo)> = vec![];
     for lib in libs {
         let Some(lib_entry) = &lib.entry else {
             continue;
         };
 
-        let (defined, undefined) = get_symbols(lib_entry).unwrap_or_default();
-        for symbol in defined {
+        let (defined, undefined) = get_symbols(lib_entry, cache).unwrap_or_default();
+        for (symbol, is_weak) in defined {
+            if is_weak {
+                weak_defined_table
+                    .entry(symbol)
+                    .or_insert_with(|| lib.clone());
+                continue;
+            }
+
             defined_table.insert(symbol, lib.clone());
         }
 
         for symbol in undefined {
-            undefined_table.insert(symbol, lib.clone());
+            undefined_table.push((symbol, lib.clone()));
         }
     }
 
     AllSymbols {
         defined: defined_table,
+        weak_defined: weak_defined_table,
         undefined: undefined_table,
     }
 }
 
-static LIB_REGEX: LazyLock<Regex> =
+/// The static-archive naming convention to look for, driven by the target
+/// triple rather than the host platform: cross-compiling to MSVC from Linux
+/// still needs `.lib` names, and vice versa.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum LibFormat {
+    /// `libfoo.a`, read as a GNU `ar` archive of ELF or Mach-O objects. Used
+    /// by GCC/Clang on Linux and macOS, and by the GNU/LLVM linkers on
+    /// Windows (MinGW).
+    Gnu,
+    /// `foo.lib`, read as a COFF import/static-lib archive (same outer `ar`
+    /// container, no `lib` prefix). Used by the MSVC linker.
+    Msvc,
+}
+
+impl LibFormat {
+    /// Select a format from `CARGO_CFG_TARGET_ENV`-style input (`"msvc"`,
+    /// `"gnu"`, `"musl"`, ...). Anything other than `"msvc"` uses GNU naming.
+    fn for_target_env(target_env: &str) -> LibFormat {
+        if target_env == "msvc" {
+            LibFormat::Msvc
+        } else {
+            LibFormat::Gnu
+        }
+    }
+
+    fn regex(self) -> &'static Regex {
+        match self {
+            LibFormat::Gnu => &GNU_LIB_REGEX,
+            LibFormat::Msvc => &MSVC_LIB_REGEX,
+        }
+    }
+}
+
+static GNU_LIB_REGEX: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"lib(.*)\.a").expect("static lib regex failed to compile"));
 
-fn find_libs(base_path: &Path) -> AllLibs {
+static MSVC_LIB_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(.*)\.lib").expect("static lib regex failed to compile"));
+
+fn find_libs(base_path: &Path, format: LibFormat, cache: Option<&ArchiveCache>) -> AllLibs {
     let libs: HashSet<LibInfo> = WalkDir::new(base_path)
         .into_iter()
         .filter_map(|e| e.ok())
         .filter_map(|entry| entry.metadata().ok().map(|meta| (meta, entry)))
         .filter(|(metadata, _)| metadata.is_file())
-        .filter(|(_, file)| is_static_lib(file.file_name()))
+        .filter(|(_, file)| is_static_lib(file.file_name(), format))
         .filter(|(_, file)| file.file_name().to_str().is_some())
         .map(|(_, file)| {
             let name = file.file_name().to_str().unwrap().to_owned();
@@ -171,15 +361,159 @@ fn find_libs(base_path: &Path) -> AllLibs {
         })
         .collect();
 
-    let all_symbols = generate_lookup_tables(libs.clone());
+    let all_symbols = generate_lookup_tables(libs.clone(), cache);
 
     AllLibs { libs, all_symbols }
 }
 
+/// On-disk cache of parsed archive symbol tables, keyed by archive path plus
+/// size and mtime, so an unchanged incremental rebuild doesn't have to
+/// re-read and re-parse every archive member.
+struct ArchiveCache {
+    cache_dir: PathBuf,
+}
+
+impl ArchiveCache {
+    /// Create a cache rooted at `cache_dir`, typically `OUT_DIR`.
+    fn new(cache_dir: impl Into<PathBuf>) -> Self {
+        ArchiveCache {
+            cache_dir: cache_dir.into(),
+        }
+    }
+
+    fn entry_path(&self, archive_path: &Path) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        archive_path.hash(&mut hasher);
+        self.cache_dir
+            .join(format!("{:016x}.symcache", hasher.finish()))
+    }
+
+    fn load(
+        &self,
+        archive_path: &Path,
+        size: u64,
+        mtime: u64,
+    ) -> Option<(Vec<(DefinedSymbol, bool)>, Vec<UnDefinedSymbol>)> {
+        let bytes = fs::read(self.entry_path(archive_path)).ok()?;
+        decode_cache_entry(&bytes, size, mtime)
+    }
+
+    fn store(
+        &self,
+        archive_path: &Path,
+        size: u64,
+        mtime: u64,
+        defined: &[(DefinedSymbol, bool)],
+        undefined: &[UnDefinedSymbol],
+    ) {
+        if fs::create_dir_all(&self.cache_dir).is_err() {
+            return;
+        }
+
+        let bytes = encode_cache_entry(size, mtime, defined, undefined);
+        let _ = fs::write(self.entry_path(archive_path), bytes);
+    }
+}
+
+fn encode_cache_entry(
+    size: u64,
+    mtime: u64,
+    defined: &[(DefinedSymbol, bool)],
+    undefined: &[UnDefinedSymbol],
+) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&size.to_le_bytes());
+    out.extend_from_slice(&mtime.to_le_bytes());
+
+    out.extend_from_slice(&(defined.len() as u32).to_le_bytes());
+    for (symbol, is_weak) in defined {
+        out.push(*is_weak as u8);
+        out.extend_from_slice(&(symbol.symbol.len() as u32).to_le_bytes());
+        out.extend_from_slice(&symbol.symbol);
+    }
+
+    out.extend_from_slice(&(undefined.len() as u32).to_le_bytes());
+    for symbol in undefined {
+        out.extend_from_slice(&(symbol.symbol.len() as u32).to_le_bytes());
+        out.extend_from_slice(&symbol.symbol);
+    }
+
+    out
+}
+
+fn decode_cache_entry(
+    bytes: &[u8],
+    expected_size: u64,
+    expected_mtime: u64,
+) -> Option<(Vec<(DefinedSymbol, bool)>, Vec<UnDefinedSymbol>)> {
+    let mut cursor = bytes;
+
+    let size = read_u64(&mut cursor)?;
+    let mtime = read_u64(&mut cursor)?;
+    if size != expected_size || mtime != expected_mtime {
+        return None;
+    }
+
+    let defined_count = read_u32(&mut cursor)?;
+    let mut defined = Vec::with_capacity(defined_count as usize);
+    for _ in 0..defined_count {
+        let is_weak = read_u8(&mut cursor)? != 0;
+        let symbol = read_bytes(&mut cursor)?;
+        defined.push((DefinedSymbol { symbol }, is_weak));
+    }
+
+    let undefined_count = read_u32(&mut cursor)?;
+    let mut undefined = Vec::with_capacity(undefined_count as usize);
+    for _ in 0..undefined_count {
+        let symbol = read_bytes(&mut cursor)?;
+        undefined.push(UnDefinedSymbol { symbol });
+    }
+
+    Some((defined, undefined))
+}
+
+fn read_u8(cursor: &mut &[u8]) -> Option<u8> {
+    let (byte, rest) = cursor.split_first()?;
+    *cursor = rest;
+    Some(*byte)
+}
+
+fn read_u32(cursor: &mut &[u8]) -> Option<u32> {
+    if cursor.len() < 4 {
+        return None;
+    }
+    let (head, rest) = cursor.split_at(4);
+    *cursor = rest;
+    Some(u32::from_le_bytes(head.try_into().ok()?))
+}
+
+fn read_u64(cursor: &mut &[u8]) -> Option<u64> {
+    if cursor.len() < 8 {
+        return None;
+    }
+    let (head, rest) = cursor.split_at(8);
+    *cursor = rest;
+    Some(u64::from_le_bytes(head.try_into().ok()?))
+}
+
+fn read_bytes(cursor: &mut &[u8]) -> Option<Vec<u8>> {
+    let len = read_u32(cursor)? as usize;
+    if cursor.len() < len {
+        return None;
+    }
+    let (head, rest) = cursor.split_at(len);
+    *cursor = rest;
+    Some(head.to_vec())
+}
+
 #[derive(Clone)]
 struct AllSymbols {
+    /// Strong (non-weak, non-common) definitions.
     defined: HashMap<DefinedSymbol, LibInfo>,
-    undefined: HashMap<UnDefinedSymbol, LibInfo>,
+    /// Weak definitions, kept separate so duplicates among them are never
+    /// treated as a conflict. Common (tentative) symbols are not recorded.
+    weak_defined: HashMap<DefinedSymbol, LibInfo>,
+    undefined: Vec<(UnDefinedSymbol, LibInfo)>,
 }
 
 #[derive(Clone, Eq, Hash, PartialEq)]
@@ -198,10 +532,7 @@ impl From<&UnDefinedSymbol> for DefinedSymbol {
 impl Debug for DefinedSymbol {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("DefinedSymbol")
-            .field(
-                "symbol",
-                &String::from_utf8(self.symbol.clone()).unwrap_or(String::from("Not utf8")),
-            )
+            .field("symbol", &demangle(&self.symbol))
             .finish()
     }
 }
@@ -214,14 +545,17 @@ struct UnDefinedSymbol {
 impl Debug for UnDefinedSymbol {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("UnDefinedSymbol")
-            .field(
-                "symbol",
-                &String::from_utf8(self.symbol.clone()).unwrap_or(String::from("Not utf8")),
-            )
+            .field("symbol", &demangle(&self.symbol))
             .finish()
     }
 }
 
+impl Display for UnDefinedSymbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", demangle(&self.symbol))
+    }
+}
+
 #[derive(Clone)]
 struct AllLibs {
     libs: HashSet<LibInfo>,
@@ -256,11 +590,26 @@ impl Eq for LibInfo {}
 
 fn get_symbols(
     entry: &DirEntry,
-) -> Result<(Vec<DefinedSymbol>, Vec<UnDefinedSymbol>), Box<dyn Error>> {
+    cache: Option<&ArchiveCache>,
+) -> Result<(Vec<(DefinedSymbol, bool)>, Vec<UnDefinedSymbol>), Box<dyn Error>> {
+    let metadata = entry.metadata()?;
+    let size = metadata.len();
+    let mtime = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+
+    if let Some(cache) = cache {
+        if let Some(cached) = cache.load(entry.path(), size, mtime) {
+            return Ok(cached);
+        }
+    }
+
     let archive_file = File::open(entry.path())?;
     let mut archive = ar::Archive::new(archive_file);
 
-    let mut all_defined: Vec<DefinedSymbol> = vec![];
+    let mut all_defined: Vec<(DefinedSymbol, bool)> = vec![];
     let mut all_undefined: Vec<UnDefinedSymbol> = vec![];
 
     while let Some(entry) = archive.next_entry() {
@@ -274,12 +623,27 @@ fn get_symbols(
         }
 
         let file = object::File::parse(&*buf)?;
+        // Weak definitions legitimately appear in many translation units
+        // (C++ inline functions, template instantiations, vtables) and must
+        // not be treated the same as a strong definition when checking for
+        // duplicates. Common (tentative) symbols do not pass the
+        // `is_definition()` filter, so they are dropped here.
         let mut defined = file
             .symbols()
             .filter(|symbol| symbol.is_definition())
-            .filter_map(|symbol| symbol.name_bytes().ok())
-            .map(|bytes| DefinedSymbol {
-                symbol: bytes.to_vec(),
+            .filter_map(|symbol| {
+                symbol
+                    .name_bytes()
+                    .ok()
+                    .map(|bytes| (bytes, symbol.is_weak() || symbol.is_common()))
+            })
+            .map(|(bytes, is_weak)| {
+                (
+                    DefinedSymbol {
+                        symbol: bytes.to_vec(),
+                    },
+                    is_weak,
+                )
             })
             .collect();
 
@@ -296,12 +660,16 @@ fn get_symbols(
         all_undefined.append(&mut undefined);
     }
 
+    if let Some(cache) = cache {
+        cache.store(entry.path(), size, mtime, &all_defined, &all_undefined);
+    }
+
     Ok((all_defined, all_undefined))
 }
 
-fn is_static_lib(file_name: &OsStr) -> bool {
+fn is_static_lib(file_name: &OsStr, format: LibFormat) -> bool {
     let Some(file_name) = file_name.to_str() else {
         return false;
     };
-    LIB_REGEX.is_match(file_name)
+    format.regex().is_match(file_name)
 }
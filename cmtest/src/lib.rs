@@ -1,45 +1,113 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
     error::Error,
     ffi::OsStr,
     fmt::{Debug, Display},
-    fs::File,
-    hash::Hash,
+    fs::{self, File},
+    hash::{Hash, Hasher},
     io::Read,
-    path::Path,
+    path::{Path, PathBuf},
     sync::LazyLock,
+    time::UNIX_EPOCH,
 };
 
 use object::{Object, ObjectSymbol};
 use petgraph::{
-    algo::{toposort, Cycle},
+    algo::{tarjan_scc, toposort, Cycle},
     graph::NodeIndex,
+    visit::EdgeRef,
     Graph,
 };
 use regex::Regex;
 use thiserror::Error;
 use walkdir::{DirEntry, WalkDir};
 
-pub fn link_to_dependencies(ordered_deps: Vec<LibInfo>) {
-    ordered_deps.iter().for_each(|lib| {
-        println!(
-            "cargo:rustc-link-search=native={}",
-            lib.entry
-                .clone()
-                .expect("No entry exists!")
-                .path()
-                .parent()
-                .expect("lib has no parent!")
-                .to_str()
-                .unwrap()
-        );
-        println!(
-            "cargo:rustc-link-lib=static={}",
-            get_static_lib_name(&lib.name)
-                .expect("Not a static lib!")
-                .as_str()
-        );
-    });
+/// How `order_dependencies` should treat a cycle in the static-lib dependency graph.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum CycleHandling {
+    /// Collapse cycles into linker groups so the build still succeeds (the default).
+    #[default]
+    Group,
+    /// Preserve the old behavior: any cycle is a hard `DepFindError::CylicDependency`.
+    Error,
+}
+
+/// A single step in the final link order: either one archive, or a set of
+/// mutually-dependent archives that must be wrapped in linker group markers.
+#[derive(Clone, Debug)]
+pub enum LinkUnit {
+    Single(LibInfo),
+    Group(Vec<LibInfo>),
+}
+
+pub fn link_to_dependencies(ordered_deps: Vec<LinkUnit>, format: LibFormat) {
+    for unit in ordered_deps {
+        match unit {
+            LinkUnit::Single(lib) => emit_link_lib(&lib, format),
+            LinkUnit::Group(libs) => emit_link_group(&libs, format),
+        }
+    }
+}
+
+/// Emit a single archive. Goes through `rustc-link-arg` rather than
+/// `rustc-link-lib`: rustc places the `-l` flags it generates from
+/// `rustc-link-lib` *before* every `rustc-link-arg`, so if singles used
+/// `rustc-link-lib` while groups used `rustc-link-arg` (to fix their own
+/// internal ordering), every group would land to the right of every single
+/// regardless of topological order - breaking exactly the case a grouped SCC
+/// depends on a single library that must come after it. Keeping every unit
+/// on the same `rustc-link-arg` stream preserves the order
+/// `order_dependencies` computed.
+fn emit_link_lib(lib: &LibInfo, format: LibFormat) {
+    emit_link_search(lib);
+    println!("cargo:rustc-link-arg={}", link_arg_for_lib(lib, format));
+}
+
+/// Emit a set of mutually-dependent archives. `-Wl,--start-group`/
+/// `--end-group` is a GNU ld/lld/gold extension with no equivalent in
+/// MSVC's `link.exe`, so for `LibFormat::Msvc` the group degrades to the
+/// archives in plain order with no wrapping - `CycleHandling::Group` only
+/// actually breaks a cycle for GNU-style linkers.
+fn emit_link_group(libs: &[LibInfo], format: LibFormat) {
+    for lib in libs {
+        emit_link_search(lib);
+    }
+
+    if format == LibFormat::Gnu {
+        println!("cargo:rustc-link-arg=-Wl,--start-group");
+        for lib in libs {
+            println!("cargo:rustc-link-arg={}", link_arg_for_lib(lib, format));
+        }
+        println!("cargo:rustc-link-arg=-Wl,--end-group");
+    } else {
+        for lib in libs {
+            println!("cargo:rustc-link-arg={}", link_arg_for_lib(lib, format));
+        }
+    }
+}
+
+fn emit_link_search(lib: &LibInfo) {
+    println!(
+        "cargo:rustc-link-search=native={}",
+        lib.entry
+            .clone()
+            .expect("No entry exists!")
+            .path()
+            .parent()
+            .expect("lib has no parent!")
+            .to_str()
+            .unwrap()
+    );
+}
+
+/// The linker-command-line argument that selects `lib`, in `format`'s native
+/// syntax: `-lfoo` for GNU ld, `foo.lib` for MSVC `link.exe`.
+fn link_arg_for_lib(lib: &LibInfo, format: LibFormat) -> String {
+    let name = get_static_lib_name(&lib.name, format).expect("Not a static lib!");
+    match format {
+        LibFormat::Gnu => format!("-l{name}"),
+        LibFormat::Msvc => format!("{name}.lib"),
+    }
 }
 
 #[derive(Error, Debug)]
@@ -60,7 +128,10 @@ impl From<Cycle<NodeIndex>> for DepFindError {
     }
 }
 
-pub fn order_dependencies(libs: AllLibs) -> Result<Vec<LibInfo>, DepFindError> {
+pub fn order_dependencies(
+    libs: AllLibs,
+    cycle_handling: CycleHandling,
+) -> Result<Vec<LinkUnit>, DepFindError> {
     let mut dep_graph = Graph::<LibInfo, u8>::new();
 
     let mut index_to_lib_map: HashMap<NodeIndex, LibInfo> = HashMap::new();
@@ -73,7 +144,11 @@ pub fn order_dependencies(libs: AllLibs) -> Result<Vec<LibInfo>, DepFindError> {
 
     let mut dependencies: HashSet<Dependency> = HashSet::new();
     for (symbol, dependent) in libs.all_symbols.undefined {
-        let Some(dependency) = get_lib_for_symbol(&symbol, &libs.all_symbols.defined) else {
+        let Some(dependency) = get_lib_for_symbol(
+            &symbol,
+            &libs.all_symbols.defined,
+            &libs.all_symbols.weak_defined,
+        ) else {
             continue;
         };
 
@@ -99,18 +174,74 @@ pub fn order_dependencies(libs: AllLibs) -> Result<Vec<LibInfo>, DepFindError> {
         dep_graph.add_edge(dep.dependent, dep.dependency, 0);
     }
 
-    let ordered_deps = toposort(&dep_graph, None)?;
+    match cycle_handling {
+        CycleHandling::Error => {
+            let ordered_deps = toposort(&dep_graph, None)?;
+            Ok(ordered_deps
+                .into_iter()
+                .filter_map(|index| index_to_lib_map.get(&index).cloned())
+                .map(LinkUnit::Single)
+                .collect())
+        }
+        CycleHandling::Group => Ok(order_with_groups(&dep_graph, &index_to_lib_map)),
+    }
+}
 
-    let mut ordered_libs: Vec<LibInfo> = vec![];
+/// Resolve the dependency graph into link order even when it contains cycles, by
+/// contracting each strongly-connected component into a single condensation node
+/// (guaranteed acyclic), toposorting that, then emitting a `LinkUnit::Group` for
+/// any component with more than one member so callers can wrap it in linker
+/// `--start-group`/`--end-group` markers.
+fn order_with_groups(
+    dep_graph: &Graph<LibInfo, u8>,
+    index_to_lib_map: &HashMap<NodeIndex, LibInfo>,
+) -> Vec<LinkUnit> {
+    let sccs = tarjan_scc(dep_graph);
+
+    let mut node_to_scc: HashMap<NodeIndex, usize> = HashMap::new();
+    for (scc_index, members) in sccs.iter().enumerate() {
+        for node in members {
+            node_to_scc.insert(*node, scc_index);
+        }
+    }
 
-    for index in ordered_deps {
-        match index_to_lib_map.get(&index) {
-            Some(lib) => ordered_libs.push(lib.clone()),
-            None => continue,
+    let mut condensation = Graph::<(), ()>::new();
+    for _ in &sccs {
+        condensation.add_node(());
+    }
+
+    let mut condensation_edges: HashSet<(usize, usize)> = HashSet::new();
+    for edge in dep_graph.edge_references() {
+        let source_scc = node_to_scc[&edge.source()];
+        let target_scc = node_to_scc[&edge.target()];
+        if source_scc != target_scc {
+            condensation_edges.insert((source_scc, target_scc));
         }
     }
 
-    Ok(ordered_libs)
+    for (source_scc, target_scc) in condensation_edges {
+        condensation.add_edge(NodeIndex::new(source_scc), NodeIndex::new(target_scc), ());
+    }
+
+    let condensation_order =
+        toposort(&condensation, None).expect("condensation graph must be acyclic");
+
+    condensation_order
+        .into_iter()
+        .filter_map(|condensation_index| {
+            let members = &sccs[condensation_index.index()];
+            let mut libs: Vec<LibInfo> = members
+                .iter()
+                .filter_map(|node| index_to_lib_map.get(node).cloned())
+                .collect();
+
+            if libs.len() <= 1 {
+                libs.pop().map(LinkUnit::Single)
+            } else {
+                Some(LinkUnit::Group(libs))
+            }
+        })
+        .collect()
 }
 
 struct Dependency {
@@ -136,15 +267,131 @@ impl Eq for Dependency {}
 fn get_lib_for_symbol(
     symbol: &UnDefinedSymbol,
     defined_libs: &HashMap<DefinedSymbol, LibInfo>,
+    weak_defined_libs: &HashMap<DefinedSymbol, LibInfo>,
 ) -> Option<LibInfo> {
-    defined_libs.get(&symbol.into()).cloned()
+    let key = DefinedSymbol::from(symbol);
+    defined_libs
+        .get(&key)
+        .or_else(|| weak_defined_libs.get(&key))
+        .cloned()
 }
 
-pub fn generate_lookup_tables<I>(libs: I) -> Result<AllSymbols, DepFindError>
+/// Well-known libc/runtime symbols mapped to the system library that
+/// provides them, so common external references don't get reported as
+/// genuinely unresolved. Prefixes end in `_` and match by prefix; anything
+/// else matches the symbol name exactly.
+static SYSTEM_SYMBOLS: LazyLock<Vec<(&'static str, &'static str)>> = LazyLock::new(|| {
+    vec![
+        ("pthread_", "pthread"),
+        ("dlopen", "dl"),
+        ("dlsym", "dl"),
+        ("dlclose", "dl"),
+        ("dladdr", "dl"),
+        ("sin", "m"),
+        ("cos", "m"),
+        ("tan", "m"),
+        ("sqrt", "m"),
+        ("pow", "m"),
+        ("exp", "m"),
+        ("log", "m"),
+        ("clock_gettime", "rt"),
+        ("shm_open", "rt"),
+        ("shm_unlink", "rt"),
+    ]
+});
+
+fn system_lib_for_symbol(name: &str) -> Option<&'static str> {
+    SYSTEM_SYMBOLS.iter().find_map(|(pattern, lib)| {
+        if let Some(prefix) = pattern.strip_suffix('_') {
+            name.starts_with(prefix).then_some(*lib)
+        } else {
+            (name == *pattern).then_some(*lib)
+        }
+    })
+}
+
+/// The result of scanning for undefined symbols that no discovered archive
+/// satisfies.
+#[derive(Clone, Debug, Default)]
+pub struct UnresolvedReport {
+    /// Undefined symbols with no known provider, grouped by the library that
+    /// needs them.
+    pub unresolved: HashMap<LibInfo, Vec<UnDefinedSymbol>>,
+    /// System (dylib) libraries that satisfy the well-known symbols found,
+    /// e.g. `pthread`, `m`, `dl`, `rt`.
+    pub system_libs: HashSet<String>,
+}
+
+impl UnresolvedReport {
+    /// Human-readable summary suitable for a `cargo:warning=` line or a panic message.
+    pub fn describe(&self) -> String {
+        self.unresolved
+            .iter()
+            .map(|(lib, symbols)| {
+                format!(
+                    "{} unresolved symbol(s) needed by {lib}: {}",
+                    symbols.len(),
+                    symbols
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// Scan `all_symbols.undefined` for references satisfied by neither a strong
+/// nor a weak definition in any discovered archive. Symbols matching
+/// [`SYSTEM_SYMBOLS`] are classified as system-library needs instead of
+/// genuinely unresolved, so the link won't fail for a missing `-lpthread` the
+/// same way it would for a forgotten dependency or a typo.
+pub fn find_unresolved_symbols(all_symbols: &AllSymbols) -> UnresolvedReport {
+    let mut report = UnresolvedReport::default();
+
+    for (symbol, dependent) in &all_symbols.undefined {
+        let key = DefinedSymbol::from(symbol);
+        if all_symbols.defined.contains_key(&key) || all_symbols.weak_defined.contains_key(&key) {
+            continue;
+        }
+
+        let name = String::from_utf8_lossy(&symbol.symbol);
+        if let Some(system_lib) = system_lib_for_symbol(&name) {
+            report.system_libs.insert(system_lib.to_owned());
+            continue;
+        }
+
+        // `all_symbols.undefined` has one entry per (symbol, object-member),
+        // so the same symbol can be referenced from several members of the
+        // same archive - dedup per lib so `describe()` doesn't repeat a name.
+        let symbols_for_lib = report.unresolved.entry(dependent.clone()).or_default();
+        if !symbols_for_lib.contains(symbol) {
+            symbols_for_lib.push(symbol.clone());
+        }
+    }
+
+    report
+}
+
+/// Emit `cargo:rustc-link-lib=dylib=...` for every system library identified
+/// by [`find_unresolved_symbols`].
+pub fn link_system_libs(report: &UnresolvedReport) {
+    for lib in &report.system_libs {
+        println!("cargo:rustc-link-lib=dylib={lib}");
+    }
+}
+
+pub fn generate_lookup_tables<I>(
+    libs: I,
+    cache: Option<&ArchiveCache>,
+) -> Result<AllSymbols, DepFindError>
 where
     I: IntoIterator<Item = LibInfo>,
 {
     let mut defined_table: HashMap<DefinedSymbol, LibInfo> = HashMap::new();
+    let mut weak_defined_table: HashMap<DefinedSymbol, LibInfo> = HashMap::new();
     let mut undefined_table: Vec<(UnDefinedSymbol, LibInfo)> = vec![];
     let mut undefined_symbol: Vec<UnDefinedSymbol> = vec![];
     let mut duplicates: Vec<(DefinedSymbol, LibInfo, LibInfo)> = vec![];
@@ -153,8 +400,15 @@ where
             continue;
         };
 
-        let (defined, undefined) = get_symbols(lib_entry).unwrap_or_default();
-        for symbol in defined {
+        let (defined, undefined) = get_symbols(lib_entry, cache).unwrap_or_default();
+        for (symbol, is_weak) in defined {
+            if is_weak {
+                weak_defined_table
+                    .entry(symbol)
+                    .or_insert_with(|| lib.clone());
+                continue;
+            }
+
             match defined_table.insert(symbol.clone(), lib.clone()) {
                 Some(first_define) => {
                     duplicates.push((symbol, first_define, lib.clone()));
@@ -181,33 +435,74 @@ where
 
     Ok(AllSymbols {
         defined: defined_table,
+        weak_defined: weak_defined_table,
         undefined: undefined_table,
     })
 }
 
-static LIB_REGEX: LazyLock<Regex> =
+/// The static-archive naming convention to look for, driven by the target
+/// triple rather than the host platform: cross-compiling to MSVC from Linux
+/// still needs `.lib` names, and vice versa.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LibFormat {
+    /// `libfoo.a`, read as a GNU `ar` archive of ELF or Mach-O objects. Used
+    /// by GCC/Clang on Linux and macOS, and by the GNU/LLVM linkers on
+    /// Windows (MinGW).
+    Gnu,
+    /// `foo.lib`, read as a COFF import/static-lib archive (same outer `ar`
+    /// container, no `lib` prefix). Used by the MSVC linker.
+    Msvc,
+}
+
+impl LibFormat {
+    /// Select a format from `CARGO_CFG_TARGET_ENV`-style input (`"msvc"`,
+    /// `"gnu"`, `"musl"`, ...). Anything other than `"msvc"` uses GNU naming.
+    pub fn for_target_env(target_env: &str) -> LibFormat {
+        if target_env == "msvc" {
+            LibFormat::Msvc
+        } else {
+            LibFormat::Gnu
+        }
+    }
+
+    fn regex(self) -> &'static Regex {
+        match self {
+            LibFormat::Gnu => &GNU_LIB_REGEX,
+            LibFormat::Msvc => &MSVC_LIB_REGEX,
+        }
+    }
+}
+
+static GNU_LIB_REGEX: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"lib(.*)\.a").expect("static lib regex failed to compile"));
 
-fn get_static_lib_name(file_name: &str) -> Option<String> {
-    let cap = LIB_REGEX.captures(file_name)?;
+static MSVC_LIB_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(.*)\.lib").expect("static lib regex failed to compile"));
+
+fn get_static_lib_name(file_name: &str, format: LibFormat) -> Option<String> {
+    let cap = format.regex().captures(file_name)?;
 
     Some(String::from(&cap[1]))
 }
 
-fn is_static_lib(file_name: &OsStr) -> bool {
+fn is_static_lib(file_name: &OsStr, format: LibFormat) -> bool {
     let Some(file_name) = file_name.to_str() else {
         return false;
     };
-    LIB_REGEX.is_match(file_name)
+    format.regex().is_match(file_name)
 }
 
-pub fn find_libs(base_path: &Path) -> Result<AllLibs, DepFindError> {
+pub fn find_libs(
+    base_path: &Path,
+    format: LibFormat,
+    cache: Option<&ArchiveCache>,
+) -> Result<AllLibs, DepFindError> {
     let libs: HashSet<LibInfo> = WalkDir::new(base_path)
         .into_iter()
         .filter_map(|e| e.ok())
         .filter_map(|entry| entry.metadata().ok().map(|meta| (meta, entry)))
         .filter(|(metadata, _)| metadata.is_file())
-        .filter(|(_, file)| is_static_lib(file.file_name()))
+        .filter(|(_, file)| is_static_lib(file.file_name(), format))
         .filter(|(_, file)| file.file_name().to_str().is_some())
         .map(|(_, file)| {
             let name = file.file_name().to_str().unwrap().to_owned();
@@ -218,14 +513,172 @@ pub fn find_libs(base_path: &Path) -> Result<AllLibs, DepFindError> {
         })
         .collect();
 
-    let all_symbols = generate_lookup_tables(libs.clone())?;
+    let all_symbols = generate_lookup_tables(libs.clone(), cache)?;
 
     Ok(AllLibs { libs, all_symbols })
 }
 
+/// On-disk cache of parsed archive symbol tables, keyed by archive path plus
+/// size and mtime. Re-reading and `object::File::parse`-ing every member of
+/// every `.a` on each build is a lot of I/O for a large static dependency
+/// set; this turns a no-change incremental rebuild into one metadata `stat`
+/// and one small cache-file read per archive instead of a full re-scan.
+///
+/// Callers opt in by passing `Some(&cache)` to [`find_libs`] /
+/// [`generate_lookup_tables`]; without a cache the scan behaves exactly as
+/// before.
+pub struct ArchiveCache {
+    cache_dir: PathBuf,
+}
+
+impl ArchiveCache {
+    /// Create a cache rooted at `cache_dir`, typically `OUT_DIR` in a `build.rs`.
+    pub fn new(cache_dir: impl Into<PathBuf>) -> Self {
+        ArchiveCache {
+            cache_dir: cache_dir.into(),
+        }
+    }
+
+    /// Drop the cached entry for `archive_path`, forcing the next scan to re-parse it.
+    pub fn invalidate(&self, archive_path: &Path) {
+        let _ = fs::remove_file(self.entry_path(archive_path));
+    }
+
+    fn entry_path(&self, archive_path: &Path) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        archive_path.hash(&mut hasher);
+        self.cache_dir
+            .join(format!("{:016x}.symcache", hasher.finish()))
+    }
+
+    fn load(
+        &self,
+        archive_path: &Path,
+        size: u64,
+        mtime: u64,
+    ) -> Option<(Vec<(DefinedSymbol, bool)>, Vec<UnDefinedSymbol>)> {
+        let bytes = fs::read(self.entry_path(archive_path)).ok()?;
+        decode_cache_entry(&bytes, size, mtime)
+    }
+
+    fn store(
+        &self,
+        archive_path: &Path,
+        size: u64,
+        mtime: u64,
+        defined: &[(DefinedSymbol, bool)],
+        undefined: &[UnDefinedSymbol],
+    ) {
+        if fs::create_dir_all(&self.cache_dir).is_err() {
+            return;
+        }
+
+        let bytes = encode_cache_entry(size, mtime, defined, undefined);
+        let _ = fs::write(self.entry_path(archive_path), bytes);
+    }
+}
+
+fn encode_cache_entry(
+    size: u64,
+    mtime: u64,
+    defined: &[(DefinedSymbol, bool)],
+    undefined: &[UnDefinedSymbol],
+) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&size.to_le_bytes());
+    out.extend_from_slice(&mtime.to_le_bytes());
+
+    out.extend_from_slice(&(defined.len() as u32).to_le_bytes());
+    for (symbol, is_weak) in defined {
+        out.push(*is_weak as u8);
+        out.extend_from_slice(&(symbol.symbol.len() as u32).to_le_bytes());
+        out.extend_from_slice(&symbol.symbol);
+    }
+
+    out.extend_from_slice(&(undefined.len() as u32).to_le_bytes());
+    for symbol in undefined {
+        out.extend_from_slice(&(symbol.symbol.len() as u32).to_le_bytes());
+        out.extend_from_slice(&symbol.symbol);
+    }
+
+    out
+}
+
+fn decode_cache_entry(
+    bytes: &[u8],
+    expected_size: u64,
+    expected_mtime: u64,
+) -> Option<(Vec<(DefinedSymbol, bool)>, Vec<UnDefinedSymbol>)> {
+    let mut cursor = bytes;
+
+    let size = read_u64(&mut cursor)?;
+    let mtime = read_u64(&mut cursor)?;
+    if size != expected_size || mtime != expected_mtime {
+        return None;
+    }
+
+    let defined_count = read_u32(&mut cursor)?;
+    let mut defined = Vec::with_capacity(defined_count as usize);
+    for _ in 0..defined_count {
+        let is_weak = read_u8(&mut cursor)? != 0;
+        let symbol = read_bytes(&mut cursor)?;
+        defined.push((DefinedSymbol { symbol }, is_weak));
+    }
+
+    let undefined_count = read_u32(&mut cursor)?;
+    let mut undefined = Vec::with_capacity(undefined_count as usize);
+    for _ in 0..undefined_count {
+        let symbol = read_bytes(&mut cursor)?;
+        undefined.push(UnDefinedSymbol { symbol });
+    }
+
+    Some((defined, undefined))
+}
+
+fn read_u8(cursor: &mut &[u8]) -> Option<u8> {
+    let (byte, rest) = cursor.split_first()?;
+    *cursor = rest;
+    Some(*byte)
+}
+
+fn read_u32(cursor: &mut &[u8]) -> Option<u32> {
+    if cursor.len() < 4 {
+        return None;
+    }
+    let (head, rest) = cursor.split_at(4);
+    *cursor = rest;
+    Some(u32::from_le_bytes(head.try_into().ok()?))
+}
+
+fn read_u64(cursor: &mut &[u8]) -> Option<u64> {
+    if cursor.len() < 8 {
+        return None;
+    }
+    let (head, rest) = cursor.split_at(8);
+    *cursor = rest;
+    Some(u64::from_le_bytes(head.try_into().ok()?))
+}
+
+fn read_bytes(cursor: &mut &[u8]) -> Option<Vec<u8>> {
+    let len = read_u32(cursor)? as usize;
+    if cursor.len() < len {
+        return None;
+    }
+    let (head, rest) = cursor.split_at(len);
+    *cursor = rest;
+    Some(head.to_vec())
+}
+
 #[derive(Clone)]
 pub struct AllSymbols {
+    /// Strong (non-weak, non-common) definitions. Two libraries defining the
+    /// same strong symbol is a genuine conflict.
     pub defined: HashMap<DefinedSymbol, LibInfo>,
+    /// Weak definitions. Many libraries may legitimately define the same
+    /// weak symbol, so these are kept separately and never cause a
+    /// `MultipleDefines` error, but can still satisfy a dependency edge.
+    /// Common (tentative) symbols are not recorded here or in `defined`.
+    pub weak_defined: HashMap<DefinedSymbol, LibInfo>,
     pub undefined: Vec<(UnDefinedSymbol, LibInfo)>,
 }
 
@@ -250,24 +703,36 @@ impl From<&UnDefinedSymbol> for DefinedSymbol {
     }
 }
 
+/// Demangle a symbol name for diagnostics: try C++ (Itanium) demangling
+/// first, since this crate exists to link a C++ library through `cxx` and
+/// virtually every interesting symbol is `_ZN`-mangled, then Rust demangling,
+/// falling back to the raw (lossy-utf8) name when neither applies.
+fn demangle(symbol: &[u8]) -> String {
+    if let Ok(cpp_symbol) = cpp_demangle::Symbol::new(symbol) {
+        if let Ok(demangled) = cpp_symbol.demangle(&cpp_demangle::DemangleOptions::default()) {
+            return demangled;
+        }
+    }
+
+    let raw = String::from_utf8_lossy(symbol);
+    if let Ok(rust_symbol) = rustc_demangle::try_demangle(&raw) {
+        return rust_symbol.to_string();
+    }
+
+    raw.into_owned()
+}
+
 impl Debug for DefinedSymbol {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("DefinedSymbol")
-            .field(
-                "symbol",
-                &String::from_utf8(self.symbol.clone()).unwrap_or(String::from("Not utf8")),
-            )
+            .field("symbol", &demangle(&self.symbol))
             .finish()
     }
 }
 
 impl Display for DefinedSymbol {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            &String::from_utf8(self.symbol.clone()).unwrap_or(String::from("Not utf8"))
-        )
+        write!(f, "{}", demangle(&self.symbol))
     }
 }
 
@@ -295,14 +760,17 @@ impl From<DefinedSymbol> for UnDefinedSymbol {
 impl Debug for UnDefinedSymbol {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("UnDefinedSymbol")
-            .field(
-                "symbol",
-                &String::from_utf8(self.symbol.clone()).unwrap_or(String::from("Not utf8")),
-            )
+            .field("symbol", &demangle(&self.symbol))
             .finish()
     }
 }
 
+impl Display for UnDefinedSymbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", demangle(&self.symbol))
+    }
+}
+
 #[derive(Clone)]
 pub struct AllLibs {
     pub libs: HashSet<LibInfo>,
@@ -337,11 +805,26 @@ impl Eq for LibInfo {}
 
 fn get_symbols(
     entry: &DirEntry,
-) -> Result<(Vec<DefinedSymbol>, Vec<UnDefinedSymbol>), Box<dyn Error>> {
+    cache: Option<&ArchiveCache>,
+) -> Result<(Vec<(DefinedSymbol, bool)>, Vec<UnDefinedSymbol>), Box<dyn Error>> {
+    let metadata = entry.metadata()?;
+    let size = metadata.len();
+    let mtime = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+
+    if let Some(cache) = cache {
+        if let Some(cached) = cache.load(entry.path(), size, mtime) {
+            return Ok(cached);
+        }
+    }
+
     let archive_file = File::open(entry.path())?;
     let mut archive = ar::Archive::new(archive_file);
 
-    let mut all_defined: Vec<DefinedSymbol> = vec![];
+    let mut all_defined: Vec<(DefinedSymbol, bool)> = vec![];
     let mut all_undefined: Vec<UnDefinedSymbol> = vec![];
 
     while let Some(entry) = archive.next_entry() {
@@ -355,12 +838,27 @@ fn get_symbols(
         }
 
         let file = object::File::parse(&*buf)?;
+        // Weak definitions legitimately appear in many translation units
+        // (C++ inline functions, template instantiations, vtables) and must
+        // not be treated the same as a strong definition when checking for
+        // duplicates. Common (tentative) symbols do not pass the
+        // `is_definition()` filter, so they are dropped here.
         let mut defined = file
             .symbols()
             .filter(|symbol| symbol.is_definition())
-            .filter_map(|symbol| symbol.name_bytes().ok())
-            .map(|bytes| DefinedSymbol {
-                symbol: bytes.to_vec(),
+            .filter_map(|symbol| {
+                symbol
+                    .name_bytes()
+                    .ok()
+                    .map(|bytes| (bytes, symbol.is_weak() || symbol.is_common()))
+            })
+            .map(|(bytes, is_weak)| {
+                (
+                    DefinedSymbol {
+                        symbol: bytes.to_vec(),
+                    },
+                    is_weak,
+                )
             })
             .collect();
 
@@ -377,5 +875,69 @@ fn get_symbols(
         all_undefined.append(&mut undefined);
     }
 
+    if let Some(cache) = cache {
+        cache.store(entry.path(), size, mtime, &all_defined, &all_undefined);
+    }
+
     Ok((all_defined, all_undefined))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object::write::{Object as WriteObject, SectionKind, Symbol, SymbolSection};
+    use object::{Architecture, BinaryFormat, Endianness, SymbolFlags, SymbolKind, SymbolScope};
+
+    /// Build a one-object-member `.a` archive containing a single weak
+    /// definition named `symbol_name`, written under `dir`.
+    fn write_archive_with_weak_symbol(dir: &Path, archive_name: &str, symbol_name: &[u8]) {
+        let mut object =
+            WriteObject::new(BinaryFormat::Elf, Architecture::X86_64, Endianness::Little);
+        let text = object.add_section(Vec::new(), b".text".to_vec(), SectionKind::Text);
+        object.append_section_data(text, &[0u8; 4], 4);
+        object.add_symbol(Symbol {
+            name: symbol_name.to_vec(),
+            value: 0,
+            size: 4,
+            kind: SymbolKind::Text,
+            scope: SymbolScope::Linkage,
+            weak: true,
+            section: SymbolSection::Section(text),
+            flags: SymbolFlags::None,
+        });
+        let object_bytes = object.write().expect("failed to write test object");
+
+        let archive_file =
+            File::create(dir.join(archive_name)).expect("failed to create test archive");
+        let mut builder = ar::Builder::new(archive_file);
+        let header = ar::Header::new(b"member.o".to_vec(), object_bytes.len() as u64);
+        builder
+            .append(&header, &*object_bytes)
+            .expect("failed to append test object to archive");
+    }
+
+    #[test]
+    fn weak_duplicate_definitions_do_not_raise_multiple_defines() {
+        let dir = std::env::temp_dir().join(format!(
+            "cmtest-weak-dup-test-{:?}",
+            std::thread::current().id()
+        ));
+        std::fs::create_dir_all(&dir).expect("failed to create temp dir");
+
+        write_archive_with_weak_symbol(&dir, "liba.a", b"shared_weak_fn");
+        write_archive_with_weak_symbol(&dir, "libb.a", b"shared_weak_fn");
+
+        let result = find_libs(&dir, LibFormat::Gnu, None);
+
+        std::fs::remove_dir_all(&dir).ok();
+
+        let all_libs = result.expect("weak duplicate symbols must not produce a DepFindError");
+        assert!(all_libs
+            .all_symbols
+            .weak_defined
+            .contains_key(&DefinedSymbol {
+                symbol: b"shared_weak_fn".to_vec(),
+            }));
+        assert!(all_libs.all_symbols.defined.is_empty());
+    }
+}
@@ -1,4 +1,7 @@
-use cmtest::{find_libs, link_to_dependencies, order_dependencies};
+use cmtest::{
+    find_libs, find_unresolved_symbols, link_system_libs, link_to_dependencies, order_dependencies,
+    ArchiveCache, CycleHandling, LibFormat,
+};
 use std::path::Path;
 
 fn main() {
@@ -17,7 +20,11 @@ fn main() {
     println!("cargo:rustc-link-search=native={}", lib.display());
     println!("cargo:rustc-link-lib=static=tinkwrap");
 
-    let all_libs = find_libs(Path::new(&lib.display().to_string()));
+    let format =
+        LibFormat::for_target_env(&std::env::var("CARGO_CFG_TARGET_ENV").unwrap_or_default());
+    let cache = ArchiveCache::new(std::env::var("OUT_DIR").expect("OUT_DIR not set"));
+    let all_libs = find_libs(Path::new(&lib.display().to_string()), format, Some(&cache))
+        .expect("Failed to scan for static libs");
 
     all_libs
         .all_symbols
@@ -35,8 +42,15 @@ fn main() {
         println!("Found static lib: {}", lib);
     }
 
-    let ordered_libs = order_dependencies(all_libs);
+    let unresolved = find_unresolved_symbols(&all_libs.all_symbols);
+    link_system_libs(&unresolved);
+    if !unresolved.unresolved.is_empty() {
+        println!("cargo:warning={}", unresolved.describe());
+    }
+
+    let ordered_libs = order_dependencies(all_libs, CycleHandling::Group)
+        .expect("Failed to resolve dependency order");
 
     println!("Ordered dependencies: {:?}", ordered_libs);
-    link_to_dependencies(ordered_libs);
+    link_to_dependencies(ordered_libs, format);
 }